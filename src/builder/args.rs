@@ -0,0 +1,149 @@
+//! Typed, named argument declarations for actions added with
+//! [`BuilderChain::add_action_with_args`](../trait.BuilderChain.html#tymethod.add_action_with_args).
+
+use std::collections::HashMap;
+
+/// The primitive type a [`Param`](struct.Param.html) converts its matching token into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamType {
+	/// A `String` value.
+	String,
+	/// An `i32` value.
+	Int,
+	/// An `f64` value.
+	Float,
+	/// A `bool` value.
+	Bool,
+}
+
+/// A value produced by converting a token to a [`Param`](struct.Param.html)'s declared
+/// [`ParamType`](enum.ParamType.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	/// A `String` value.
+	Str(String),
+	/// An `i32` value.
+	Int(i32),
+	/// An `f64` value.
+	Float(f64),
+	/// A `bool` value.
+	Bool(bool),
+	/// Every value matched by a variadic parameter.
+	List(Vec<Value>),
+}
+
+/// A named, typed argument declaration.
+///
+/// Build one with [`Param::new`](struct.Param.html#method.new), optionally chaining
+/// [`optional`](struct.Param.html#method.optional) or
+/// [`variadic`](struct.Param.html#method.variadic).
+#[derive(Debug, Clone)]
+pub struct Param {
+	name: String,
+	kind: ParamType,
+	optional: bool,
+	variadic: bool,
+}
+
+impl Param {
+	/// A required parameter of the given name and type.
+	pub fn new(name: &str, kind: ParamType) -> Self {
+		Param {
+			name: name.to_string(),
+			kind,
+			optional: false,
+			variadic: false,
+		}
+	}
+
+	/// Marks the parameter as optional. If a matching token is not supplied, the parameter is
+	/// simply absent from the resulting [`Args`](struct.Args.html).
+	pub fn optional(mut self) -> Self {
+		self.optional = true;
+		self
+	}
+
+	/// Marks the parameter as variadic, consuming every remaining token as a
+	/// [`Value::List`](enum.Value.html#variant.List). Only meaningful as the last parameter in a
+	/// spec: any parameter after it receives no tokens, so a required one always fails to match.
+	pub fn variadic(mut self) -> Self {
+		self.variadic = true;
+		self
+	}
+}
+
+/// The typed arguments parsed for an action, keyed by [`Param`](struct.Param.html) name.
+#[derive(Debug, Default)]
+pub struct Args {
+	values: HashMap<String, Value>,
+}
+
+impl Args {
+	/// The value parsed for the named parameter, if it was supplied.
+	pub fn get(&self, name: &str) -> Option<&Value> {
+		self.values.get(name)
+	}
+}
+
+/// Matches `tokens` against `params`, converting each to its declared type.
+///
+/// Returns a formatted error message, suitable for writing straight to a user, on a missing
+/// required parameter, a failed conversion, or surplus tokens beyond the declared params.
+pub(crate) fn parse(params: &[Param], tokens: &[&str]) -> Result<Args, String> {
+	let mut values = HashMap::new();
+	let mut idx = 0;
+
+	for param in params {
+		if param.variadic {
+			let rest = &tokens[idx.min(tokens.len())..];
+
+			if rest.is_empty() {
+				if !param.optional {
+					return Err(format!("missing required argument '{}'", param.name));
+				}
+			} else {
+				let mut list = Vec::with_capacity(rest.len());
+				for token in rest {
+					list.push(convert(param, token)?);
+				}
+				values.insert(param.name.clone(), Value::List(list));
+			}
+
+			idx = tokens.len();
+			continue;
+		}
+
+		match tokens.get(idx) {
+			Some(token) => {
+				values.insert(param.name.clone(), convert(param, token)?);
+				idx += 1;
+			}
+			None if param.optional => (),
+			None => return Err(format!("missing required argument '{}'", param.name)),
+		}
+	}
+
+	if idx < tokens.len() {
+		return Err(format!("unexpected argument(s): {}", tokens[idx..].join(" ")));
+	}
+
+	Ok(Args { values })
+}
+
+fn convert(param: &Param, token: &str) -> Result<Value, String> {
+	match param.kind {
+		ParamType::String => Ok(Value::Str(token.to_string())),
+		ParamType::Int => token
+			.parse()
+			.map(Value::Int)
+			.map_err(|_| format!("'{}' is not a valid integer for '{}'", token, param.name)),
+		ParamType::Float => token
+			.parse()
+			.map(Value::Float)
+			.map_err(|_| format!("'{}' is not a valid float for '{}'", token, param.name)),
+		ParamType::Bool => token
+			.parse()
+			.map(Value::Bool)
+			.map_err(|_| format!("'{}' is not a valid bool for '{}'", token, param.name)),
+	}
+}
@@ -0,0 +1,82 @@
+//! Subprocess actions added with
+//! [`BuilderChain::add_command_action`](../trait.BuilderChain.html#tymethod.add_command_action).
+
+use std::io::{Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+
+/// The normalized outcome of a subprocess spawned by an action added with
+/// [`add_command_action`](../trait.BuilderChain.html#tymethod.add_command_action).
+///
+/// `Ok(())` for a zero exit code; `Err(code)` for anything else, so results are comparable across
+/// platforms regardless of whether the process exited, was signaled, or something else entirely.
+pub type CommandStatus = Result<(), i32>;
+
+/// Runs `program base_args... arguments...`, writing its stdout and then its stderr into `wtr` and
+/// returning its normalized exit status. Both are collected in full and written once the child has
+/// closed them; nothing is written to `wtr` while the child is still producing output.
+pub(crate) fn run(
+	program: &str,
+	base_args: &[String],
+	arguments: &[&str],
+	wtr: &mut dyn Write,
+) -> CommandStatus {
+	let mut child = match Command::new(program)
+		.args(base_args)
+		.args(arguments)
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+	{
+		Ok(child) => child,
+		Err(e) => {
+			let _ = writeln!(wtr, "failed to spawn '{}': {}", program, e);
+			return Err(-1);
+		}
+	};
+
+	// Read stdout and stderr concurrently so a full pipe buffer on one can't block the other.
+	let mut stderr = child.stderr.take().expect("stderr was piped");
+	let stderr_thread = thread::spawn(move || {
+		let mut buf = Vec::new();
+		let _ = stderr.read_to_end(&mut buf);
+		buf
+	});
+
+	let mut stdout_buf = Vec::new();
+	if let Some(mut stdout) = child.stdout.take() {
+		let _ = stdout.read_to_end(&mut stdout_buf);
+	}
+	let stderr_buf = stderr_thread.join().unwrap_or_default();
+
+	let _ = wtr.write_all(&stdout_buf);
+	let _ = wtr.write_all(&stderr_buf);
+
+	match child.wait() {
+		Ok(status) => normalize(status),
+		Err(e) => {
+			let _ = writeln!(wtr, "failed to wait on '{}': {}", program, e);
+			Err(-1)
+		}
+	}
+}
+
+fn normalize(status: ExitStatus) -> CommandStatus {
+	if status.success() {
+		return Ok(());
+	}
+
+	if let Some(code) = status.code() {
+		return Err(code);
+	}
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::process::ExitStatusExt;
+		if let Some(signal) = status.signal() {
+			return Err(-signal);
+		}
+	}
+
+	Err(-1)
+}
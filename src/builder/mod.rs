@@ -0,0 +1,260 @@
+//! Build a [`Commander`](../struct.Commander.html) by chaining calls that describe the command
+//! tree.
+//!
+//! # Example
+//! ```rust
+//! use cmdtree::*;
+//!
+//! let cmder: Commander<()> = Builder::default_config("base")
+//!     .begin_class("one", "")
+//!     .begin_class("two", "")
+//!     .into_commander()
+//!     .unwrap();
+//! ```
+
+use crate::{Action, ActionBody, Commander, SubClass};
+use std::error;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub mod args;
+pub mod command;
+
+pub use self::args::{Args, Param, ParamType, Value};
+pub use self::command::CommandStatus;
+
+/// Builds a [`Commander`](../struct.Commander.html) tree through a chain of calls.
+///
+/// Construct one with [`Builder::default_config`](struct.Builder.html#method.default_config),
+/// then use the [`BuilderChain`](trait.BuilderChain.html) methods to describe classes and
+/// actions, finishing with
+/// [`into_commander`](trait.BuilderChain.html#tymethod.into_commander).
+pub struct Builder<'a, R> {
+	stack: Vec<SubClass<'a, R>>,
+	err: Option<BuilderError>,
+	history_path: Option<PathBuf>,
+}
+
+/// An error that can occur while building a command tree.
+#[derive(Debug, PartialEq)]
+pub enum BuilderError {
+	/// A class or action of this name already exists at this level of the tree.
+	DuplicateName(String),
+}
+
+impl fmt::Display for BuilderError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BuilderError::DuplicateName(name) => {
+				write!(f, "a class or action named '{}' already exists", name)
+			}
+		}
+	}
+}
+
+impl error::Error for BuilderError {}
+
+/// Chainable methods for describing a command tree.
+///
+/// Implemented for [`Builder`](struct.Builder.html) so that each step simply returns the next
+/// `Builder`, letting calls be chained without intermediate bindings.
+pub trait BuilderChain<'a, R> {
+	/// Begin a new class nested under the current position in the tree, moving into it.
+	fn begin_class(self, name: &str, help_msg: &'a str) -> Builder<'a, R>;
+
+	/// Close out the current class, attaching it to its parent and moving back up a level.
+	fn end_class(self) -> Builder<'a, R>;
+
+	/// Add an action to the current class.
+	///
+	/// `action` is run with the writer passed to
+	/// [`parse_line`](../struct.Commander.html#method.parse_line) and the arguments that followed
+	/// the action's name.
+	fn add_action<F>(self, name: &str, help_msg: &'a str, action: F) -> Builder<'a, R>
+	where
+		F: FnMut(&mut dyn Write, &[&str]) -> R + 'a;
+
+	/// Add an action with a declared argument spec to the current class.
+	///
+	/// The tokens following the action's name are matched against `params` and converted to
+	/// their declared types before `action` is run; on a missing required argument or a failed
+	/// conversion, a formatted error is written to the caller's writer and `action` is not run.
+	fn add_action_with_args<F>(
+		self,
+		name: &str,
+		help_msg: &'a str,
+		params: Vec<Param>,
+		action: F,
+	) -> Builder<'a, R>
+	where
+		F: FnMut(&mut dyn Write, &Args) -> R + 'a;
+
+	/// Add an action that shells out to a subprocess.
+	///
+	/// `command` is a template such as `"git log"`: split on whitespace, its words become the
+	/// program and its leading arguments, with the tokens following the action's name appended
+	/// after them. The child's stdout and stderr are collected and written into the caller's
+	/// writer (stdout first) once the child has closed them, not while it runs, and its
+	/// exit status is normalized into a [`CommandStatus`](command/type.CommandStatus.html) and
+	/// converted to `R`.
+	fn add_command_action(self, name: &str, help_msg: &'a str, command: &str) -> Builder<'a, R>
+	where
+		R: From<CommandStatus>;
+
+	/// Finalize the builder into a runnable [`Commander`](../struct.Commander.html).
+	fn into_commander(self) -> Result<Commander<'a, R>, BuilderError>;
+}
+
+impl<'a, R> Builder<'a, R> {
+	/// Start building a command tree, naming the root class.
+	pub fn default_config(root_name: &str) -> Builder<'a, R> {
+		Builder {
+			stack: vec![SubClass::with_name(root_name, "")],
+			err: None,
+			history_path: None,
+		}
+	}
+
+	/// Opt in to loading and saving interactive history to `path`, via
+	/// [`Commander::run`](../struct.Commander.html#method.run) and
+	/// [`Commander::run_with_completion`](../struct.Commander.html#method.run_with_completion).
+	/// History is loaded once before the loop starts and saved once after it exits;
+	/// consecutive duplicate lines are not recorded twice.
+	pub fn with_history_path(mut self, path: impl Into<PathBuf>) -> Builder<'a, R> {
+		self.history_path = Some(path.into());
+		self
+	}
+
+	fn record_duplicate(&mut self, name: &str) {
+		if self.err.is_none() {
+			self.err = Some(BuilderError::DuplicateName(name.to_string()));
+		}
+	}
+
+	fn name_taken(&mut self, name: &str) -> bool {
+		let current = self.stack.last().expect("stack is never empty");
+		let taken = current.classes.iter().any(|c| c.name == name)
+			|| current.actions.iter().any(|a| a.name == name);
+
+		if taken {
+			self.record_duplicate(name);
+		}
+
+		taken
+	}
+
+	fn push_action(&mut self, action: Action<'a, R>) {
+		self.stack
+			.last_mut()
+			.expect("stack is never empty")
+			.actions
+			.push(action);
+	}
+}
+
+impl<'a, R> BuilderChain<'a, R> for Builder<'a, R> {
+	fn begin_class(mut self, name: &str, help_msg: &'a str) -> Builder<'a, R> {
+		self.stack.push(SubClass::with_name(name, help_msg));
+		self
+	}
+
+	fn end_class(mut self) -> Builder<'a, R> {
+		if self.stack.len() < 2 {
+			return self;
+		}
+
+		let child = self.stack.pop().expect("checked length above");
+		let parent = self.stack.last_mut().expect("checked length above");
+
+		let duplicate = parent.classes.iter().any(|c| c.name == child.name)
+			|| parent.actions.iter().any(|a| a.name == child.name);
+
+		if duplicate {
+			self.record_duplicate(&child.name);
+		} else {
+			parent.classes.push(Rc::new(child));
+		}
+
+		self
+	}
+
+	fn add_action<F>(mut self, name: &str, help_msg: &'a str, action: F) -> Builder<'a, R>
+	where
+		F: FnMut(&mut dyn Write, &[&str]) -> R + 'a,
+	{
+		let lower = name.to_lowercase();
+
+		if self.name_taken(&lower) {
+			return self;
+		}
+
+		self.push_action(Action {
+			name: lower,
+			help: help_msg,
+			body: ActionBody::Raw(std::cell::RefCell::new(Box::new(action))),
+		});
+
+		self
+	}
+
+	fn add_action_with_args<F>(
+		mut self,
+		name: &str,
+		help_msg: &'a str,
+		params: Vec<Param>,
+		action: F,
+	) -> Builder<'a, R>
+	where
+		F: FnMut(&mut dyn Write, &Args) -> R + 'a,
+	{
+		let lower = name.to_lowercase();
+
+		if self.name_taken(&lower) {
+			return self;
+		}
+
+		self.push_action(Action {
+			name: lower,
+			help: help_msg,
+			body: ActionBody::Typed(params, std::cell::RefCell::new(Box::new(action))),
+		});
+
+		self
+	}
+
+	fn add_command_action(self, name: &str, help_msg: &'a str, command: &str) -> Builder<'a, R>
+	where
+		R: From<CommandStatus>,
+	{
+		let mut parts = command.split_whitespace();
+		let program = parts.next().unwrap_or_default().to_string();
+		let base_args: Vec<String> = parts.map(str::to_string).collect();
+
+		self.add_action(name, help_msg, move |wtr, arguments| {
+			R::from(self::command::run(&program, &base_args, arguments, wtr))
+		})
+	}
+
+	fn into_commander(mut self) -> Result<Commander<'a, R>, BuilderError> {
+		while self.stack.len() > 1 {
+			self = self.end_class();
+		}
+
+		if let Some(err) = self.err {
+			return Err(err);
+		}
+
+		let root = self.stack.pop().expect("stack is never empty");
+		let path = root.name.clone();
+		let root = Rc::new(root);
+
+		Ok(Commander {
+			root: Rc::clone(&root),
+			current: root,
+			path,
+			history_path: self.history_path,
+		})
+	}
+}
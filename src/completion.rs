@@ -0,0 +1,193 @@
+//! Tab-completion support for an interactive [`Commander`](../struct.Commander.html) session.
+//!
+//! `run()` registers no completer, so pressing `Tab` does nothing. Use
+//! [`Commander::run_with_completion`](../struct.Commander.html#method.run_with_completion)
+//! instead, handing it a closure that builds a [`linefeed::Completer`](https://docs.rs/linefeed/0.6.0/linefeed/complete/trait.Completer.html)
+//! from the `Commander` — [`TreeCompleter`](struct.TreeCompleter.html) is a tree-aware one
+//! suitable for most uses, and also suggests from history (see its docs).
+
+use crate::parse::LineResult;
+use crate::{Commander, TreeNode};
+use colored::*;
+use linefeed::complete::{Completer, Completion};
+use linefeed::prompter::Prompter;
+use linefeed::terminal::DefaultTerminal;
+use linefeed::{Interface, ReadResult};
+use std::sync::Arc;
+
+/// Reserved words understood at the root of every line, regardless of tree position.
+const NAV_WORDS: &[&str] = &["exit", "cancel", "c", "help"];
+
+/// A tree-aware [`linefeed::Completer`](https://docs.rs/linefeed/0.6.0/linefeed/complete/trait.Completer.html).
+///
+/// Built from an owned [`TreeNode`](../struct.TreeNode.html) snapshot (see
+/// [`Commander::tree_snapshot`](../struct.Commander.html#method.tree_snapshot)) rather than a
+/// borrow of the live tree, so that it satisfies `linefeed`'s `Send + Sync` bound on completers.
+///
+/// Given the words already typed on the line, it descends the snapshot one word at a time
+/// (splitting on whitespace) to find the node the cursor is in, then offers that node's class and
+/// action names as candidates. A `.`-separated word such as `one.two` is understood as a single
+/// path, descending through `one` then `two`.
+///
+/// It also looks at the interface's own history (via
+/// [`Prompter::history`](https://docs.rs/linefeed/0.6.0/linefeed/prompter/struct.Prompter.html#method.history)):
+/// the most recent prior line that had a matching word in the same position is offered first,
+/// ahead of the tree's own names — `linefeed` has no dedicated hint display, so surfacing it as
+/// the leading completion candidate is the closest equivalent it offers.
+pub struct TreeCompleter {
+	tree: TreeNode,
+}
+
+impl TreeCompleter {
+	/// Build a completer from a snapshot of the tree at the current position.
+	pub fn new(tree: TreeNode) -> Self {
+		TreeCompleter { tree }
+	}
+}
+
+impl Completer<DefaultTerminal> for TreeCompleter {
+	fn complete(
+		&self,
+		word: &str,
+		prompter: &Prompter<DefaultTerminal>,
+		start: usize,
+		_end: usize,
+	) -> Option<Vec<Completion>> {
+		let preceding = &prompter.buffer()[..start];
+		let word_idx = preceding.split_whitespace().count();
+
+		let mut candidates: Vec<String> = Vec::new();
+
+		let hint = prompter
+			.history()
+			.rev()
+			.find_map(|line| line.split_whitespace().nth(word_idx).filter(|w| w.starts_with(word)));
+		candidates.extend(hint.map(str::to_string));
+
+		if let Some(node) = resolve(&self.tree, preceding) {
+			candidates.extend(node.class_names().chain(node.action_names()).map(str::to_string));
+		}
+
+		if preceding.split_whitespace().next().is_none() {
+			candidates.extend(NAV_WORDS.iter().map(|w| w.to_string()));
+		}
+
+		let mut seen = std::collections::HashSet::new();
+		let completions: Vec<Completion> = candidates
+			.into_iter()
+			.filter(|c| c.starts_with(word) && seen.insert(c.clone()))
+			.map(Completion::simple)
+			.collect();
+
+		if completions.is_empty() {
+			None
+		} else {
+			Some(completions)
+		}
+	}
+}
+
+/// Descend `tree` by the already-typed words in `preceding`, understanding both the space and
+/// `.` separators, returning the node reached.
+fn resolve<'t>(tree: &'t TreeNode, preceding: &str) -> Option<&'t TreeNode> {
+	let mut node = tree;
+
+	for word in preceding.split_whitespace() {
+		for segment in word.split('.').filter(|s| !s.is_empty()) {
+			node = node.descend(segment)?;
+		}
+	}
+
+	Some(node)
+}
+
+impl<'r, R> Commander<'r, R> {
+	/// Run the `Commander` interactively with tab-completion.
+	///
+	/// `build_completer` is called at the start of each loop iteration with the `Commander`,
+	/// letting the returned completer reflect the current position in the tree. See
+	/// [`TreeCompleter`](struct.TreeCompleter.html) for a tree-aware default.
+	///
+	/// Otherwise behaves as [`run`](../struct.Commander.html#method.run), including its history
+	/// loading/saving.
+	pub fn run_with_completion<F, C>(mut self, build_completer: F)
+	where
+		F: Fn(&Self) -> C,
+		C: Completer<DefaultTerminal> + 'static,
+	{
+		let interface = Interface::new("commander").expect("failed to start interface");
+		let mut exit = false;
+
+		if let Some(path) = self.history_path() {
+			let _ = interface.load_history(path);
+		}
+
+		while !exit {
+			interface.set_completer(Arc::new(build_completer(&self)));
+
+			interface
+				.set_prompt(&format!("{}=> ", self.path().bright_cyan()))
+				.expect("failed to set prompt");
+
+			if let Ok(ReadResult::Input(s)) = interface.read_line() {
+				interface.add_history_unique(s.clone());
+
+				if let LineResult::Exit = self.parse_line(&s, true, &mut std::io::stdout()) {
+					exit = true;
+				}
+			}
+		}
+
+		if let Some(path) = self.history_path() {
+			let _ = interface.save_history(path);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tree() -> TreeNode {
+		TreeNode {
+			name: "base".to_string(),
+			classes: vec![TreeNode {
+				name: "one".to_string(),
+				classes: vec![TreeNode {
+					name: "two".to_string(),
+					classes: Vec::new(),
+					actions: vec!["leaf".to_string()],
+				}],
+				actions: Vec::new(),
+			}],
+			actions: vec!["root-action".to_string()],
+		}
+	}
+
+	#[test]
+	fn resolve_root_test() {
+		let tree = tree();
+		let node = resolve(&tree, "").unwrap();
+		assert_eq!(node.name(), "base");
+	}
+
+	#[test]
+	fn resolve_space_separated_test() {
+		let tree = tree();
+		let node = resolve(&tree, "one two ").unwrap();
+		assert_eq!(node.name(), "two");
+	}
+
+	#[test]
+	fn resolve_dotted_path_test() {
+		let tree = tree();
+		let node = resolve(&tree, "one.two ").unwrap();
+		assert_eq!(node.name(), "two");
+	}
+
+	#[test]
+	fn resolve_unknown_segment_test() {
+		let tree = tree();
+		assert!(resolve(&tree, "one three ").is_none());
+	}
+}
@@ -16,40 +16,48 @@ use colored::*;
 use linefeed::{Interface, ReadResult};
 use std::cell::RefCell;
 use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 pub mod builder;
+pub mod completion;
 mod parse;
 
 use self::parse::LineResult;
 pub use builder::{Builder, BuilderChain};
+pub use completion::TreeCompleter;
 
 /// A constructed command tree.
 ///
-/// Most of the time a user will want to use `run()` which will handle all the parsing and navigating of the tree.
-/// Alternatively, `parse_line` can be used to simulate a read input and update the command tree position.
+/// `R` is the type returned by every action in the tree (see
+/// [`Action::call`](struct.Action.html)). Most of the time a user will want to use `run()` which
+/// will handle all the parsing and navigating of the tree, writing action output to `stdout`.
+/// Alternatively, `parse_line` can be used to simulate a read input against a caller-supplied
+/// writer and update the command tree position, capturing the value an action returns.
 ///
 /// To construct a command tree, look at the [`builder` module](./builder).
-pub struct Commander<'r> {
-	root: Rc<SubClass<'r>>,
-	current: Rc<SubClass<'r>>,
+pub struct Commander<'r, R> {
+	root: Rc<SubClass<'r, R>>,
+	current: Rc<SubClass<'r, R>>,
 	path: String,
+	history_path: Option<PathBuf>,
 }
 
-impl<'r> Commander<'r> {
+impl<'r, R> Commander<'r, R> {
 	/// Return the path of the current class, separated by `.`.
 	///
 	/// # Example
 	/// ```rust
 	/// use cmdtree::*;
-	/// let mut cmder = Builder::default_config("base")
-	///		.begin_class("one", "")
-	///		.begin_class("two", "")
-	///		.into_commander().unwrap();
+	/// let mut cmder: Commander<()> = Builder::default_config("base")
+	///     .begin_class("one", "")
+	///     .begin_class("two", "")
+	///     .into_commander().unwrap();
 	///
-	///	assert_eq!(cmder.path(), "base");
-	///	cmder.parse_line("one two", true,  &mut std::io::sink());
-	///	assert_eq!(cmder.path(), "base.one.two");
+	/// assert_eq!(cmder.path(), "base");
+	/// cmder.parse_line("one two", true,  &mut std::io::sink());
+	/// assert_eq!(cmder.path(), "base.one.two");
 	/// ```
 	pub fn path(&self) -> &str {
 		&self.path
@@ -59,37 +67,98 @@ impl<'r> Commander<'r> {
 	/// Consumes the instance, and blocks the thread until the loop is exited.
 	/// Reads from `stdin` using [`linefeed::Interface`](https://docs.rs/linefeed/0.5.4/linefeed/interface/struct.Interface.html).
 	///
+	/// If a [`history_path`](struct.Commander.html#method.history_path) was configured (see
+	/// [`Builder::with_history_path`](builder/struct.Builder.html#method.with_history_path)),
+	/// history is loaded from it before the loop starts, each accepted line is recorded
+	/// (consecutive duplicates are not recorded twice), and it is saved back on exit.
+	///
 	/// This is the most simple way of using a `Commander`.
 	pub fn run(mut self) {
 		let interface = Interface::new("commander").expect("failed to start interface");
 		let mut exit = false;
 
+		if let Some(path) = &self.history_path {
+			let _ = interface.load_history(path);
+		}
+
 		while !exit {
 			interface
 				.set_prompt(&format!("{}=> ", self.path().bright_cyan()))
 				.expect("failed to set prompt");
 
-			match interface.read_line() {
-				Ok(ReadResult::Input(s)) => match self.parse_line(&s, true, &mut std::io::stdout())
-				{
-					LineResult::Exit => exit = true,
-					_ => (),
-				},
-				_ => (),
+			if let Ok(ReadResult::Input(s)) = interface.read_line() {
+				interface.add_history_unique(s.clone());
+
+				if let LineResult::Exit = self.parse_line(&s, true, &mut std::io::stdout()) {
+					exit = true;
+				}
 			}
 		}
+
+		if let Some(path) = &self.history_path {
+			let _ = interface.save_history(path);
+		}
+	}
+
+	/// An owned, read-only snapshot of the tree at the current position.
+	///
+	/// Useful for building a [`linefeed::Completer`](https://docs.rs/linefeed/0.6.0/linefeed/complete/trait.Completer.html) without
+	/// needing access to the crate's internal `Rc`-based tree.
+	pub fn tree_snapshot(&self) -> TreeNode {
+		self.current.snapshot()
+	}
+
+	/// The path history is loaded from and saved to, if one was configured with
+	/// [`Builder::with_history_path`](builder/struct.Builder.html#method.with_history_path).
+	pub fn history_path(&self) -> Option<&Path> {
+		self.history_path.as_deref()
+	}
+}
+
+/// An owned, read-only view of a single node in a command tree.
+///
+/// Obtained through [`Commander::tree_snapshot`](struct.Commander.html#method.tree_snapshot).
+/// Exists so that code outside this crate (such as a custom
+/// [`linefeed::Completer`](https://docs.rs/linefeed/0.6.0/linefeed/complete/trait.Completer.html)) can inspect the names reachable
+/// from a point in the tree without reaching into the private, `Rc`-based `SubClass` structure.
+#[derive(Debug, Clone, Default)]
+pub struct TreeNode {
+	name: String,
+	classes: Vec<TreeNode>,
+	actions: Vec<String>,
+}
+
+impl TreeNode {
+	/// The name of this node.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Names of the classes that are children of this node.
+	pub fn class_names(&self) -> impl Iterator<Item = &str> {
+		self.classes.iter().map(|c| c.name.as_str())
+	}
+
+	/// Names of the actions that belong to this node.
+	pub fn action_names(&self) -> impl Iterator<Item = &str> {
+		self.actions.iter().map(|a| a.as_str())
+	}
+
+	/// The child class of the given name, if one exists.
+	pub fn descend(&self, name: &str) -> Option<&TreeNode> {
+		self.classes.iter().find(|c| c.name == name)
 	}
 }
 
 #[derive(Debug, PartialEq)]
-struct SubClass<'a> {
+struct SubClass<'a, R> {
 	name: String,
 	help: &'a str,
-	classes: Vec<Rc<SubClass<'a>>>,
-	actions: Vec<Action<'a>>,
+	classes: Vec<Rc<SubClass<'a, R>>>,
+	actions: Vec<Action<'a, R>>,
 }
 
-impl<'a> SubClass<'a> {
+impl<'a, R> SubClass<'a, R> {
 	fn with_name(name: &str, help_msg: &'a str) -> Self {
 		SubClass {
 			name: name.to_lowercase(),
@@ -98,37 +167,77 @@ impl<'a> SubClass<'a> {
 			actions: Vec::new(),
 		}
 	}
+
+	fn snapshot(&self) -> TreeNode {
+		TreeNode {
+			name: self.name.clone(),
+			classes: self.classes.iter().map(|c| c.snapshot()).collect(),
+			actions: self.actions.iter().map(|a| a.name.clone()).collect(),
+		}
+	}
+}
+
+type ActionClosure<'a, R> = RefCell<Box<dyn FnMut(&mut dyn Write, &[&str]) -> R + 'a>>;
+type TypedActionClosure<'a, R> = RefCell<Box<dyn FnMut(&mut dyn Write, &builder::args::Args) -> R + 'a>>;
+
+/// How an [`Action`](struct.Action.html) converts the tokens following its name into the values
+/// its closure receives.
+enum ActionBody<'a, R> {
+	/// The closure receives the raw, unvalidated tokens.
+	Raw(ActionClosure<'a, R>),
+	/// Tokens are matched against `params` and converted to typed
+	/// [`Args`](builder/struct.Args.html) before the closure is run.
+	Typed(Vec<builder::args::Param>, TypedActionClosure<'a, R>),
 }
 
-struct Action<'a> {
+struct Action<'a, R> {
 	name: String,
 	help: &'a str,
-	closure: RefCell<Box<FnMut(&[&str]) + 'a>>,
+	body: ActionBody<'a, R>,
 }
 
-impl<'a> Action<'a> {
-	fn call(&self, arguments: &[&str]) {
-		let c = &mut *self.closure.borrow_mut();
-		c(arguments);
+impl<'a, R> Action<'a, R> {
+	/// Runs the action, returning `None` without invoking its closure if `arguments` failed to
+	/// match a declared argument spec (writing an explanation to `wtr` in that case).
+	fn call(&self, wtr: &mut dyn Write, arguments: &[&str]) -> Option<R> {
+		match &self.body {
+			ActionBody::Raw(closure) => {
+				let c = &mut *closure.borrow_mut();
+				Some(c(wtr, arguments))
+			}
+			ActionBody::Typed(params, closure) => match builder::args::parse(params, arguments) {
+				Ok(args) => {
+					let c = &mut *closure.borrow_mut();
+					Some(c(wtr, &args))
+				}
+				Err(msg) => {
+					let _ = writeln!(wtr, "{}", msg);
+					None
+				}
+			},
+		}
 	}
 
 	#[cfg(test)]
-	fn blank_fn(name: &str, help_msg: &'a str) -> Self {
+	fn blank_fn(name: &str, help_msg: &'a str) -> Self
+	where
+		R: Default,
+	{
 		Action {
 			name: name.to_lowercase(),
 			help: help_msg,
-			closure: RefCell::new(Box::new(|_| ())),
+			body: ActionBody::Raw(RefCell::new(Box::new(|_, _| R::default()))),
 		}
 	}
 }
 
-impl<'a> PartialEq for Action<'a> {
+impl<'a, R> PartialEq for Action<'a, R> {
 	fn eq(&self, other: &Self) -> bool {
 		self.name == other.name && self.help == other.help
 	}
 }
 
-impl<'a> fmt::Debug for Action<'a> {
+impl<'a, R> fmt::Debug for Action<'a, R> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "Action {{ name: {}, help: {} }}", self.name, self.help)
 	}
@@ -140,14 +249,14 @@ mod tests {
 
 	#[test]
 	fn subclass_with_name_test() {
-		let sc = SubClass::with_name("NAME", "Help Message");
+		let sc = SubClass::<()>::with_name("NAME", "Help Message");
 		assert_eq!(&sc.name, "name");
 		assert_eq!(sc.help, "Help Message");
 	}
 
 	#[test]
 	fn action_debug_test() {
-		let a = Action::blank_fn("action-name", "help me!");
+		let a = Action::<()>::blank_fn("action-name", "help me!");
 		assert_eq!(
 			&format!("{:?}", a),
 			"Action { name: action-name, help: help me! }"
@@ -156,7 +265,7 @@ mod tests {
 
 	#[test]
 	fn current_path_test() {
-		let mut cmder = Builder::default_config("base")
+		let mut cmder: Commander<()> = Builder::default_config("base")
 			.begin_class("one", "")
 			.begin_class("two", "")
 			.into_commander()
@@ -175,4 +284,125 @@ mod tests {
 		cmder.parse_line("one", true, w);
 		assert_eq!(cmder.path(), "base.one");
 	}
+
+	#[test]
+	fn reserved_words_and_path_nav_test() {
+		let mut cmder: Commander<()> = Builder::default_config("base")
+			.begin_class("one", "")
+			.begin_class("two", "")
+			.into_commander()
+			.unwrap();
+
+		let w = &mut std::io::sink();
+
+		cmder.parse_line("one.two", true, w);
+		assert_eq!(cmder.path(), "base.one.two");
+		assert_eq!(cmder.parse_line("cancel", true, w), LineResult::Cancel);
+		assert_eq!(cmder.path(), "base");
+
+		cmder.parse_line("one two", true, w);
+		assert_eq!(cmder.parse_line(".", true, w), LineResult::Class);
+		assert_eq!(cmder.path(), "base");
+
+		assert_eq!(cmder.parse_line("help", true, w), LineResult::Help);
+		assert_eq!(cmder.parse_line("exit", true, w), LineResult::Exit);
+	}
+
+	#[test]
+	fn typed_args_test() {
+		use builder::args::{Param, ParamType, Value};
+
+		let mut cmder: Commander<i32> = Builder::default_config("base")
+			.add_action_with_args(
+				"add",
+				"add two numbers",
+				vec![Param::new("a", ParamType::Int), Param::new("b", ParamType::Int)],
+				|_, args| {
+					let a = match args.get("a") {
+						Some(Value::Int(n)) => *n,
+						_ => 0,
+					};
+					let b = match args.get("b") {
+						Some(Value::Int(n)) => *n,
+						_ => 0,
+					};
+					a + b
+				},
+			)
+			.into_commander()
+			.unwrap();
+
+		let w = &mut std::io::sink();
+
+		assert_eq!(
+			cmder.parse_line("add 1 2", true, w).action_result(),
+			Some(3)
+		);
+		assert_eq!(cmder.parse_line("add 1", true, w), LineResult::BadArgs);
+		assert_eq!(
+			cmder.parse_line("add one two", true, w),
+			LineResult::BadArgs
+		);
+		assert_eq!(
+			cmder.parse_line("add 1 2 3", true, w),
+			LineResult::BadArgs
+		);
+	}
+
+	#[test]
+	fn bad_args_does_not_move_path_test() {
+		use builder::args::{Param, ParamType};
+
+		let mut cmder: Commander<()> = Builder::default_config("base")
+			.begin_class("one", "")
+			.add_action_with_args(
+				"add",
+				"add two numbers",
+				vec![Param::new("a", ParamType::Int), Param::new("b", ParamType::Int)],
+				|_, _| (),
+			)
+			.into_commander()
+			.unwrap();
+
+		let w = &mut std::io::sink();
+
+		assert_eq!(cmder.parse_line("one add 1", true, w), LineResult::BadArgs);
+		assert_eq!(cmder.path(), "base");
+	}
+
+	#[test]
+	fn history_path_test() {
+		let cmder: Commander<()> = Builder::default_config("base")
+			.with_history_path("/tmp/cmdtree-history-path-test")
+			.into_commander()
+			.unwrap();
+
+		assert_eq!(
+			cmder.history_path(),
+			Some(std::path::Path::new("/tmp/cmdtree-history-path-test"))
+		);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn command_action_test() {
+		use builder::CommandStatus;
+
+		let mut cmder: Commander<CommandStatus> = Builder::default_config("base")
+			.add_command_action("ok", "always succeeds", "true")
+			.add_command_action("err", "always fails", "false")
+			.into_commander()
+			.unwrap();
+
+		let w = &mut std::io::sink();
+
+		assert_eq!(
+			cmder.parse_line("ok", true, w).action_result(),
+			Some(Ok(()))
+		);
+		assert_eq!(
+			cmder.parse_line("err", true, w).action_result(),
+			Some(Err(1))
+		);
+	}
 }
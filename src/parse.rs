@@ -0,0 +1,151 @@
+//! Parsing of input lines against a [`Commander`](../struct.Commander.html)'s tree.
+
+use crate::{Commander, SubClass};
+use std::io::Write;
+use std::rc::Rc;
+
+/// The outcome of parsing a line of input with
+/// [`Commander::parse_line`](../struct.Commander.html#method.parse_line).
+#[derive(Debug, PartialEq)]
+pub enum LineResult<R> {
+	/// An action was found and run, returning the value it produced.
+	Action(R),
+	/// The input navigated to a class within the tree.
+	Class,
+	/// The input was not recognized as a class or action.
+	Unrecognized,
+	/// An action was found, but the supplied arguments did not match its declared spec. The
+	/// action's closure was not run; an explanation was written to the caller's writer.
+	BadArgs,
+	/// Returned to the root class, via `cancel`/`c`.
+	Cancel,
+	/// Help text was printed, via `help`.
+	Help,
+	/// Exit the `run` loop, via `exit`.
+	Exit,
+}
+
+impl<R> LineResult<R> {
+	/// Unwraps the value of an [`Action`](enum.LineResult.html#variant.Action) variant, discarding
+	/// every other variant.
+	///
+	/// Useful for programmatic callers of
+	/// [`parse_line`](../struct.Commander.html#method.parse_line) that only care about the values
+	/// returned by actions.
+	pub fn action_result(self) -> Option<R> {
+		match self {
+			LineResult::Action(r) => Some(r),
+			_ => None,
+		}
+	}
+}
+
+impl<'r, R> Commander<'r, R> {
+	/// Parse a line of input, navigating the tree or running an action.
+	///
+	/// Reserved words are matched first, regardless of tree position: `help` prints the current
+	/// class's help text and a listing of its child classes and actions; `cancel`/`c` returns to
+	/// the root class; `exit` ends the [`run`](struct.Commander.html#method.run) loop.
+	///
+	/// Otherwise the line is split on whitespace and walked word by word against the tree,
+	/// descending into matching classes until an action name is found and run, or a word matches
+	/// neither. A word may itself be a `.`-separated path (e.g. `one.two`); a leading `.` (on the
+	/// first word) resolves from the root rather than the current class.
+	///
+	/// `print_err` controls whether an unrecognized line is written to `wtr` as an error message.
+	/// Output of a run action is also written to `wtr`.
+	///
+	/// # Example
+	/// See [`Commander::path`](struct.Commander.html#method.path).
+	pub fn parse_line<W: Write>(&mut self, line: &str, print_err: bool, wtr: &mut W) -> LineResult<R> {
+		let words: Vec<&str> = line.split_whitespace().collect();
+
+		if words.is_empty() {
+			return LineResult::Unrecognized;
+		}
+
+		match words[0] {
+			"exit" => return LineResult::Exit,
+			"cancel" | "c" => {
+				self.current = Rc::clone(&self.root);
+				self.path = self.root.name.clone();
+				return LineResult::Cancel;
+			}
+			"help" => {
+				print_help(&self.current, wtr);
+				return LineResult::Help;
+			}
+			_ => (),
+		}
+
+		let mut class = Rc::clone(&self.current);
+		let mut path = self.path.clone();
+		let mut idx = 0;
+
+		while idx < words.len() {
+			let word = words[idx];
+
+			if idx == 0 && word.starts_with('.') {
+				class = Rc::clone(&self.root);
+				path = self.root.name.clone();
+			}
+
+			let mut matched = true;
+			for segment in word.split('.').filter(|s| !s.is_empty()) {
+				if let Some(child) = find_class(&class, segment) {
+					class = child;
+					path.push('.');
+					path.push_str(&class.name);
+				} else if let Some(action) = find_action(&class, segment) {
+					let result = action.call(wtr, &words[idx + 1..]);
+					return match result {
+						Some(result) => {
+							self.current = class;
+							self.path = path;
+							LineResult::Action(result)
+						}
+						None => LineResult::BadArgs,
+					};
+				} else {
+					matched = false;
+					break;
+				}
+			}
+
+			if !matched {
+				if print_err {
+					let _ = writeln!(wtr, "'{}' is not recognized as a class or action", word);
+				}
+				return LineResult::Unrecognized;
+			}
+
+			idx += 1;
+		}
+
+		self.current = class;
+		self.path = path;
+		LineResult::Class
+	}
+}
+
+fn find_class<'a, R>(class: &Rc<SubClass<'a, R>>, name: &str) -> Option<Rc<SubClass<'a, R>>> {
+	class
+		.classes
+		.iter()
+		.find(|c| c.name == name)
+		.map(Rc::clone)
+}
+
+fn find_action<'s, 'a, R>(class: &'s SubClass<'a, R>, name: &str) -> Option<&'s crate::Action<'a, R>> {
+	class.actions.iter().find(|a| a.name == name)
+}
+
+fn print_help<'a, R, W: Write>(class: &SubClass<'a, R>, wtr: &mut W) {
+	let _ = writeln!(wtr, "{}", class.help);
+	for c in class.classes.iter() {
+		let _ = writeln!(wtr, "  {} - {}", c.name, c.help);
+	}
+	for a in class.actions.iter() {
+		let _ = writeln!(wtr, "  {} - {}", a.name, a.help);
+	}
+}